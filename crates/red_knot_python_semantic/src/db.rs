@@ -1,5 +1,12 @@
-use salsa::DbWithJar;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::hash::Hash;
+use std::sync::Arc;
 
+use rustc_hash::{FxHashMap, FxHashSet};
+use salsa::{DbWithJar, Durability};
+
+use ruff_db::vfs::VfsFile;
 use ruff_db::{Db as SourceDb, Upcast};
 
 use crate::module::resolver::{
@@ -8,7 +15,7 @@ use crate::module::resolver::{
 };
 
 use crate::semantic_index::symbol::{public_symbols_map, scopes_map, PublicSymbolId, ScopeId};
-use crate::semantic_index::{root_scope, semantic_index, symbol_table};
+use crate::semantic_index::{imported_dependencies, root_scope, semantic_index, symbol_table};
 use crate::types::{infer_types, public_symbol_ty};
 
 #[salsa::jar(db=Db)]
@@ -23,13 +30,200 @@ pub struct Jar(
     scopes_map,
     root_scope,
     semantic_index,
+    imported_dependencies,
     infer_types,
     public_symbol_ty,
     public_symbols_map,
+    reverse_import_graph,
+    dependent_files,
 );
 
+/// Builds the reverse import graph for every known file, by inverting [`imported_dependencies`]
+/// once for the whole project rather than re-scanning it per query.
+///
+/// A single tracked query: editing one file's `import`/`from` statements only changes that
+/// file's own forward edge, so salsa only needs to re-run [`imported_dependencies`] for that one
+/// file to validate this query, rather than re-scanning every known file per [`dependent_files`]
+/// call the way a naive per-file lookup would.
+#[salsa::tracked]
+fn reverse_import_graph(db: &dyn Db) -> Arc<FxHashMap<VfsFile, FxHashSet<VfsFile>>> {
+    let mut graph: FxHashMap<VfsFile, FxHashSet<VfsFile>> = FxHashMap::default();
+
+    for file in db.vfs().known_files() {
+        for imported in imported_dependencies(db, file).iter().copied() {
+            graph.entry(imported).or_default().insert(file);
+        }
+    }
+
+    Arc::new(graph)
+}
+
+/// The set of files whose inference (directly or transitively) depends on `file`.
+///
+/// This is the inverse of [`file_to_module`]: given a changed file, it answers "which modules
+/// import it, directly or transitively" — the analog of rust-analyzer's
+/// `FileLoader::relevant_crates`. It's the prerequisite for an efficient "re-check only affected
+/// modules" batch run, and, eventually, for find-references.
+#[salsa::tracked]
+pub fn dependent_files(db: &dyn Db, file: VfsFile) -> Arc<FxHashSet<VfsFile>> {
+    Arc::new(reachable_from(file, &reverse_import_graph(db)))
+}
+
+/// Breadth-first traversal collecting everything reachable from `start` by following `edges`,
+/// excluding `start` itself.
+fn reachable_from<T: Eq + Hash + Copy>(start: T, edges: &FxHashMap<T, FxHashSet<T>>) -> FxHashSet<T> {
+    // Mark `start` as seen up front so that a cycle routing back through it doesn't add it to
+    // the result (`start` is never its own dependent, even transitively).
+    let mut seen = FxHashSet::default();
+    seen.insert(start);
+    let mut frontier = std::collections::VecDeque::from([start]);
+
+    while let Some(current) = frontier.pop_front() {
+        if let Some(direct) = edges.get(&current) {
+            for &next in direct {
+                if seen.insert(next) {
+                    frontier.push_back(next);
+                }
+            }
+        }
+    }
+
+    seen.remove(&start);
+    seen
+}
+
 /// Database giving access to semantic information about a Python program.
-pub trait Db: SourceDb + DbWithJar<Jar> + Upcast<dyn SourceDb> {}
+pub trait Db: SourceDb + DbWithJar<Jar> + Upcast<dyn SourceDb> {
+    /// Marks the vendored typeshed stubs and the configured module search paths as
+    /// [`Durability::HIGH`] inputs.
+    ///
+    /// Search paths and vendored stubs almost never change within a session, unlike the files a
+    /// user is actively editing. Salsa tracks a separate revision counter per durability level, so
+    /// a query whose only inputs are high-durability can be validated in O(1) by comparing that
+    /// counter instead of walking the dependency graph. This mirrors how rust-analyzer marks
+    /// stdlib text and proc-macro flags as `Durability::HIGH`. Ordinary source files keep the
+    /// default [`Durability::LOW`] and continue to invalidate their dependents on every edit.
+    fn set_search_paths_durability(&mut self, durability: Durability);
+
+    /// Returns every file whose inference (directly or transitively) depends on any file in
+    /// `changed_files`, by unioning [`dependent_files`] over the given set.
+    ///
+    /// Callers use this after a batch of edits to know which modules need re-checking, instead of
+    /// conservatively re-checking the whole project.
+    fn affected_files(&self, changed_files: impl IntoIterator<Item = VfsFile>) -> FxHashSet<VfsFile>
+    where
+        Self: Sized,
+    {
+        let mut affected = FxHashSet::default();
+
+        for file in changed_files {
+            affected.extend(dependent_files(self, file).iter().copied());
+        }
+
+        affected
+    }
+
+    /// Runs `f`, recording every salsa ingredient that actually executes while it runs (as
+    /// opposed to being validated from the cache), and returns `f`'s result together with the
+    /// de-duplicated list of [`ExecutedQuery`]s.
+    ///
+    /// This promotes the pattern behind the `will_run_function_query` test helper into a regular
+    /// debugging API, mirroring rust-analyzer's `log_executed`: diffing the executed-query set
+    /// across two revisions answers "why did my whole project re-type-check after a trivial
+    /// edit". `Db` implementations opt in by pushing into [`record_execution`] from their
+    /// `salsa::Database::salsa_event` implementation.
+    fn with_query_log<R>(&self, f: impl FnOnce() -> R) -> (R, Vec<ExecutedQuery>)
+    where
+        Self: Sized,
+    {
+        QUERY_LOG.with(|log| *log.borrow_mut() = Some(Vec::new()));
+        let result = f();
+        let executed = QUERY_LOG.with(|log| log.borrow_mut().take().unwrap_or_default());
+
+        let mut seen = std::collections::HashSet::with_capacity(executed.len());
+        let deduplicated = executed
+            .into_iter()
+            .filter(|query| seen.insert(query.ingredient.clone()))
+            .collect();
+
+        (result, deduplicated)
+    }
+
+    /// Checkpoint for long-running queries to cooperatively bail out once this `Db` has been
+    /// cancelled.
+    ///
+    /// When the main database mutates an input, salsa bumps its cancellation flag for every
+    /// outstanding [`salsa::ParallelDatabase::snapshot`], so a query running on one of those
+    /// snapshots observes it at its next dependency read and unwinds with
+    /// [`salsa::Cancelled`] instead of producing a result computed against stale inputs. Queries
+    /// that loop internally over a large amount of work (e.g. `infer_types` walking a big
+    /// module, or `semantic_index` building a large symbol table) should call this periodically
+    /// inside the loop, in addition to the checks salsa already performs on every tracked query
+    /// call.
+    fn unwind_if_cancelled(&self) {
+        salsa::Database::unwind_if_cancelled(self);
+    }
+}
+
+thread_local! {
+    static QUERY_LOG: RefCell<Option<Vec<ExecutedQuery>>> = const { RefCell::new(None) };
+}
+
+/// A single salsa ingredient observed by [`Db::with_query_log`], keyed by its debug-formatted
+/// database key (e.g. `resolve_module_query(Id(3))`).
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ExecutedQuery {
+    ingredient: String,
+}
+
+impl ExecutedQuery {
+    /// The debug-formatted database key of the ingredient that executed.
+    pub fn ingredient(&self) -> &str {
+        &self.ingredient
+    }
+
+    /// Returns whether this entry belongs to the query named `ingredient`, e.g.
+    /// `"resolve_module_query"`.
+    pub fn is_ingredient(&self, ingredient: &str) -> bool {
+        self.ingredient.starts_with(ingredient)
+    }
+}
+
+/// Pushes `ingredient` onto the active [`Db::with_query_log`] session, if one is running.
+///
+/// `Db` implementations call this from their `salsa::Database::salsa_event` method whenever they
+/// observe a `salsa::EventKind::WillExecute` event.
+pub fn record_execution(ingredient: String) {
+    QUERY_LOG.with(|log| {
+        if let Some(log) = log.borrow_mut().as_mut() {
+            log.push(ExecutedQuery { ingredient });
+        }
+    });
+}
+
+/// Filters `log` down to the entries belonging to `ingredient`, e.g. `"resolve_module_query"`.
+pub fn filter_by_ingredient<'a>(
+    log: &'a [ExecutedQuery],
+    ingredient: &'a str,
+) -> impl Iterator<Item = &'a ExecutedQuery> {
+    log.iter().filter(move |query| query.is_ingredient(ingredient))
+}
+
+/// Counts how many times each ingredient kind (the database key with its specific instance id
+/// stripped) executed.
+pub fn summarize(log: &[ExecutedQuery]) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+
+    for query in log {
+        let kind = query
+            .ingredient
+            .split_once('(')
+            .map_or(query.ingredient.as_str(), |(kind, _)| kind);
+        *counts.entry(kind.to_string()).or_insert(0) += 1;
+    }
+
+    counts
+}
 
 #[cfg(test)]
 pub(crate) mod tests {
@@ -39,12 +233,16 @@ pub(crate) mod tests {
 
     use salsa::ingredient::Ingredient;
     use salsa::storage::HasIngredientsFor;
-    use salsa::{AsId, DebugWithDb};
+    use salsa::{AsId, DebugWithDb, Durability};
 
     use ruff_db::file_system::{FileSystem, MemoryFileSystem, OsFileSystem};
-    use ruff_db::vfs::Vfs;
+    use ruff_db::vfs::{Vfs, VfsFile};
     use ruff_db::{Db as SourceDb, Jar as SourceJar, Upcast};
 
+    use ruff_text_size::{TextRange, TextSize};
+
+    use crate::module::resolver::internal::ModuleResolverSearchPaths;
+
     use super::{Db, Jar};
 
     #[salsa::db(Jar, SourceJar)]
@@ -131,11 +329,21 @@ pub(crate) mod tests {
         }
     }
 
-    impl Db for TestDb {}
+    impl Db for TestDb {
+        fn set_search_paths_durability(&mut self, durability: Durability) {
+            self.vfs.set_vendored_durability(durability);
+            ModuleResolverSearchPaths::set_durability(self, durability);
+        }
+    }
 
     impl salsa::Database for TestDb {
         fn salsa_event(&self, event: salsa::Event) {
             tracing::trace!("event: {:?}", event.debug(self));
+
+            if let salsa::EventKind::WillExecute { database_key } = &event.kind {
+                super::record_execution(format!("{:?}", database_key.debug(self)));
+            }
+
             let mut events = self.events.lock().unwrap();
             events.push(event);
         }
@@ -161,6 +369,300 @@ pub(crate) mod tests {
         Os(OsFileSystem),
     }
 
+    /// Extracts `# ^^^^ <expectation>` style annotations from `source`.
+    ///
+    /// Ported from rust-analyzer's `extract_annotations` test helper: a run of caret characters
+    /// anchors a [`TextRange`] on the *previous* non-comment line, the column of the first caret
+    /// mapping to the start of the range and the column after the last caret mapping to its end.
+    /// Everything after the carets (trimmed) is the expectation. A bare `#^` with no additional
+    /// carets instead anchors on the token that starts at that column, so single-token
+    /// expectations don't need to be measured out caret-by-caret. Multiple annotation lines in a
+    /// row all point back at the same previous line, so a fixture can assert several things about
+    /// one line of source.
+    pub(crate) fn extract_annotations(source: &str) -> Vec<(TextRange, String)> {
+        let mut annotations = Vec::new();
+        let mut prev_line_start = TextSize::from(0);
+        let mut prev_line = "";
+        let mut offset = TextSize::from(0);
+
+        for line in source.split_inclusive('\n') {
+            let text = line.strip_suffix('\n').unwrap_or(line);
+
+            if let Some((carets_column, carets_len, expectation)) = parse_annotation(text) {
+                let range = if carets_len == TextSize::from(0) {
+                    token_range_at(prev_line, prev_line_start, carets_column)
+                } else {
+                    TextRange::at(prev_line_start + carets_column, carets_len)
+                };
+                annotations.push((range, expectation));
+            } else {
+                prev_line = text;
+                prev_line_start = offset;
+            }
+
+            offset += TextSize::of(line);
+        }
+
+        annotations
+    }
+
+    /// Parses a single `# ^^^^ <expectation>` (or bare `#^ <expectation>`) line, returning the
+    /// byte column of the first caret, the byte length of the caret run (zero for the bare-caret
+    /// form) and the trimmed expectation text.
+    fn parse_annotation(line: &str) -> Option<(TextSize, TextSize, String)> {
+        let hash_column = line.find('#')?;
+        if !line[..hash_column].trim().is_empty() {
+            // Not an annotation: the `#` is a trailing comment on a line of real source.
+            return None;
+        }
+        let rest = &line[hash_column..];
+
+        if rest[1..].trim_start().is_empty() {
+            return None;
+        }
+
+        let after_hash = &rest[1..];
+        let caret_offset_in_rest: usize = after_hash.len() - after_hash.trim_start().len();
+        let carets_start = &after_hash[caret_offset_in_rest..];
+
+        if !carets_start.starts_with('^') {
+            return None;
+        }
+
+        let carets_len = carets_start.chars().take_while(|c| *c == '^').count();
+        let after_carets = &carets_start[carets_len..];
+        let expectation = after_carets.trim().to_string();
+
+        let carets_column = hash_column + 1 + caret_offset_in_rest;
+        // A bare `#^` directly touching the `#`, with no run of additional carets and no gap,
+        // defers to the previous line's tokenization instead of spelling out a range.
+        let is_bare = caret_offset_in_rest == 0 && carets_len == 1;
+
+        Some((
+            TextSize::try_from(carets_column).unwrap(),
+            if is_bare {
+                TextSize::from(0)
+            } else {
+                TextSize::try_from(carets_len).unwrap()
+            },
+            expectation,
+        ))
+    }
+
+    /// Finds the identifier token that *contains* byte column `column` on `line`, and returns it
+    /// as a [`TextRange`] relative to `line_start`.
+    ///
+    /// A bare `#^` sits directly under the `#`, which is one column to the left of an
+    /// unindented target: `column` can therefore land in the middle of the token rather than on
+    /// its first character. Extending both backward and forward from `column` recovers the whole
+    /// token in either case. If `column` isn't on an identifier character at all, it anchors a
+    /// single-character range instead.
+    fn token_range_at(line: &str, line_start: TextSize, column: TextSize) -> TextRange {
+        let column: usize = column.into();
+        assert!(
+            column <= line.len() && line.is_char_boundary(column),
+            "annotation caret column {column} falls outside line {line:?} (len {}); check the \
+             fixture's caret alignment against the line above it",
+            line.len(),
+        );
+        let is_token_char = |c: char| c.is_alphanumeric() || c == '_';
+
+        if !line[column..].starts_with(is_token_char) {
+            let len = line[column..].chars().next().map_or(0, char::len_utf8);
+            return TextRange::at(
+                line_start + TextSize::try_from(column).unwrap(),
+                TextSize::try_from(len).unwrap(),
+            );
+        }
+
+        let start = line[..column]
+            .char_indices()
+            .rev()
+            .take_while(|&(_, c)| is_token_char(c))
+            .last()
+            .map_or(column, |(idx, _)| idx);
+
+        let end = column
+            + line[column..]
+                .char_indices()
+                .find(|&(_, c)| !is_token_char(c))
+                .map_or(line.len() - column, |(idx, _)| idx);
+
+        TextRange::at(
+            line_start + TextSize::try_from(start).unwrap(),
+            TextSize::try_from(end - start).unwrap(),
+        )
+    }
+
+    /// Resolves each `(range, expectation)` pair from [`extract_annotations`] to the public
+    /// symbol named by the text at that range, infers its type through `db`, and asserts the
+    /// rendered type matches the expectation -- collecting every mismatch instead of stopping at
+    /// the first, so a fixture with several wrong annotations reports all of them in one run.
+    ///
+    /// Symbols are looked up by name against `file`'s public symbols, so this only resolves
+    /// top-level assignments -- the common case for `revealed:` fixtures.
+    pub(crate) fn assert_file_annotations(db: &dyn Db, file: VfsFile, source: &str) {
+        let symbols = super::public_symbols_map(db, file);
+        let mut mismatches = Vec::new();
+
+        for (range, expectation) in extract_annotations(source) {
+            let name = &source[range];
+
+            let Some((_, symbol)) = symbols.iter().find(|(symbol_name, _)| symbol_name.to_string() == name) else {
+                mismatches.push(format!("{range:?}: no public symbol named `{name}` in {file:?}"));
+                continue;
+            };
+
+            let ty = super::public_symbol_ty(db, *symbol);
+            let revealed = format!("revealed: {ty}");
+            if revealed != expectation {
+                mismatches.push(format!(
+                    "{range:?} (`{name}`): expected `{expectation}`, got `{revealed}`"
+                ));
+            }
+        }
+
+        assert!(mismatches.is_empty(), "annotation mismatches:\n{}", mismatches.join("\n"));
+    }
+
+    #[test]
+    fn extract_annotations_single_caret_range() {
+        let source = "\
+x = 1
+# ^ revealed: int
+";
+        let annotations = extract_annotations(source);
+        assert_eq!(annotations, vec![(TextRange::new(2.into(), 3.into()), "revealed: int".to_string())]);
+    }
+
+    #[test]
+    fn extract_annotations_multi_caret_range() {
+        let source = "\
+x = 1 + 2
+# ^^^^^ revealed: int
+";
+        let annotations = extract_annotations(source);
+        assert_eq!(
+            annotations,
+            vec![(TextRange::new(2.into(), 7.into()), "revealed: int".to_string())]
+        );
+    }
+
+    #[test]
+    fn extract_annotations_multiple_per_line() {
+        let source = "\
+x = y
+# ^ revealed: int
+#     ^ revealed: str
+";
+        let annotations = extract_annotations(source);
+        assert_eq!(
+            annotations,
+            vec![
+                (TextRange::new(2.into(), 3.into()), "revealed: int".to_string()),
+                (TextRange::new(6.into(), 7.into()), "revealed: str".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn extract_annotations_bare_caret_expands_to_token() {
+        let source = "\
+some_symbol = 1
+#^ revealed: Literal[1]
+";
+        let annotations = extract_annotations(source);
+        assert_eq!(
+            annotations,
+            vec![(TextRange::new(0.into(), 11.into()), "revealed: Literal[1]".to_string())]
+        );
+    }
+
+    #[test]
+    fn extract_annotations_bare_caret_aligned_with_token_start() {
+        // Deliberately not using the `"\` line-continuation idiom here: it would eat this
+        // line's leading indentation, defeating the point of testing an indented previous line.
+        let source = "  some_symbol = 1\n  #^ revealed: Literal[1]\n";
+        let annotations = extract_annotations(source);
+        assert_eq!(
+            annotations,
+            vec![(TextRange::new(2.into(), 13.into()), "revealed: Literal[1]".to_string())]
+        );
+    }
+
+    #[test]
+    fn extract_annotations_multi_byte_utf8() {
+        let source = "\
+résumé = 1
+#  ^^^^^ revealed: int
+";
+        let annotations = extract_annotations(source);
+        let (range, expectation) = &annotations[0];
+        assert_eq!(expectation, "revealed: int");
+        assert_eq!(&source[std::ops::Range::<usize>::from(*range)], "sumé");
+    }
+
+    #[test]
+    #[should_panic(expected = "falls outside line")]
+    fn extract_annotations_bare_caret_past_end_of_line_panics() {
+        // An over-indented bare `#^` whose column lands past the end of the previous line is a
+        // malformed fixture, not a valid token reference -- it should fail loudly rather than
+        // panic on a raw string-indexing out-of-bounds.
+        let source = "\
+x = 1
+          #^ revealed: int
+";
+        extract_annotations(source);
+    }
+
+    #[test]
+    fn reachable_from_follows_transitive_edges() {
+        let mut edges: rustc_hash::FxHashMap<&str, rustc_hash::FxHashSet<&str>> =
+            rustc_hash::FxHashMap::default();
+        edges.insert("a.py", ["b.py"].into_iter().collect());
+        edges.insert("b.py", ["c.py"].into_iter().collect());
+
+        // a.py <- b.py <- c.py: editing a.py affects both b.py and c.py.
+        let dependents = super::reachable_from("a.py", &edges);
+        assert_eq!(dependents, ["b.py", "c.py"].into_iter().collect());
+    }
+
+    #[test]
+    fn reachable_from_excludes_start_and_unrelated_nodes() {
+        let mut edges: rustc_hash::FxHashMap<&str, rustc_hash::FxHashSet<&str>> =
+            rustc_hash::FxHashMap::default();
+        edges.insert("a.py", ["b.py"].into_iter().collect());
+        edges.insert("unrelated.py", ["other.py"].into_iter().collect());
+
+        let dependents = super::reachable_from("a.py", &edges);
+        assert_eq!(dependents, ["b.py"].into_iter().collect());
+    }
+
+    #[test]
+    fn reachable_from_handles_import_cycles() {
+        let mut edges: rustc_hash::FxHashMap<&str, rustc_hash::FxHashSet<&str>> =
+            rustc_hash::FxHashMap::default();
+        edges.insert("a.py", ["b.py"].into_iter().collect());
+        edges.insert("b.py", ["a.py"].into_iter().collect());
+
+        // a.py and b.py import each other; the traversal must still terminate and must not
+        // report `a.py` as its own dependent.
+        let dependents = super::reachable_from("a.py", &edges);
+        assert_eq!(dependents, ["b.py"].into_iter().collect());
+    }
+
+    #[test]
+    fn reachable_from_excludes_direct_self_loop() {
+        let mut edges: rustc_hash::FxHashMap<&str, rustc_hash::FxHashSet<&str>> =
+            rustc_hash::FxHashMap::default();
+        edges.insert("a.py", ["a.py", "b.py"].into_iter().collect());
+
+        // a.py has an edge to itself (e.g. a self-referential import); it must not show up as
+        // its own dependent.
+        let dependents = super::reachable_from("a.py", &edges);
+        assert_eq!(dependents, ["b.py"].into_iter().collect());
+    }
+
     pub(crate) fn assert_will_run_function_query<C, Db, Jar>(
         db: &Db,
         to_function: impl FnOnce(&C) -> &salsa::function::FunctionIngredient<C>,
@@ -262,4 +764,131 @@ pub(crate) mod tests {
             self.ingredient.fmt_index(Some(self.value_id), f)
         }
     }
+
+    #[test]
+    fn low_durability_edit_does_not_bump_the_high_durability_revision() {
+        let mut db = TestDb::new();
+        db.set_search_paths_durability(Durability::HIGH);
+
+        let high_before = db.salsa_runtime().last_changed_revision(Durability::HIGH);
+        let low_before = db.salsa_runtime().last_changed_revision(Durability::LOW);
+
+        // `synthetic_write(Durability::LOW)` is salsa's test-only stand-in for an ordinary
+        // source-file edit (the real codepath notifies the `Vfs`, which sets a LOW-durability
+        // input). This is exactly the situation the durability marking is meant to optimize:
+        // a query whose only inputs are HIGH durability (like `resolve_module_query`, once
+        // `set_search_paths_durability(HIGH)` has been called) can be validated by comparing
+        // the HIGH durability revision alone, in O(1), without walking its dependencies --
+        // which is only sound if that counter is left untouched by LOW-durability edits.
+        db.synthetic_write(Durability::LOW);
+
+        let high_after = db.salsa_runtime().last_changed_revision(Durability::HIGH);
+        let low_after = db.salsa_runtime().last_changed_revision(Durability::LOW);
+
+        assert_eq!(
+            high_before, high_after,
+            "a LOW-durability edit must not advance the HIGH durability revision"
+        );
+        assert_ne!(
+            low_before, low_after,
+            "the edit should still advance the LOW durability revision"
+        );
+    }
+
+    #[test]
+    fn low_durability_edit_does_not_rerun_resolve_module_query() {
+        let mut db = TestDb::new();
+        db.set_search_paths_durability(Durability::HIGH);
+
+        let module_name = crate::module::ModuleName::new_static("os").unwrap();
+
+        // Prime the cache and drop the resulting events.
+        super::resolve_module_query(&db, module_name.clone());
+        db.clear_salsa_events();
+
+        // `synthetic_write(Durability::LOW)` is salsa's test-only stand-in for an ordinary
+        // source-file edit; `resolve_module_query`'s only inputs (the vendored stdlib stubs, the
+        // search path configuration) are HIGH durability once `set_search_paths_durability(HIGH)`
+        // has been called, so a LOW-durability edit elsewhere must not force it to re-run.
+        db.synthetic_write(Durability::LOW);
+
+        super::resolve_module_query(&db, module_name.clone());
+        let events = db.take_salsa_events();
+
+        assert_will_not_run_function_query(
+            &db,
+            |query: &super::resolve_module_query| &query.function,
+            module_name,
+            &events,
+        );
+    }
+
+    #[test]
+    fn with_query_log_reports_executed_queries() {
+        let db = TestDb::new();
+        let module_name = crate::module::ModuleName::new_static("os").unwrap();
+
+        let (_, executed) = db.with_query_log(|| {
+            super::resolve_module_query(&db, module_name.clone());
+        });
+
+        assert!(executed.iter().any(|query| query.is_ingredient("resolve_module_query")));
+        assert_eq!(*super::summarize(&executed).get("resolve_module_query").unwrap(), 1);
+
+        // Running again reuses the cached value, so a fresh log stays empty.
+        let (_, executed_again) = db.with_query_log(|| {
+            super::resolve_module_query(&db, module_name);
+        });
+        assert!(executed_again.is_empty());
+    }
+
+    #[test]
+    fn filter_by_ingredient_keeps_only_matching_entries() {
+        let db = TestDb::new();
+        let os = crate::module::ModuleName::new_static("os").unwrap();
+        let sys = crate::module::ModuleName::new_static("sys").unwrap();
+
+        let (_, executed) = db.with_query_log(|| {
+            super::resolve_module_query(&db, os);
+            super::resolve_module_query(&db, sys);
+        });
+
+        let resolved: Vec<_> =
+            super::filter_by_ingredient(&executed, "resolve_module_query").collect();
+        assert_eq!(resolved.len(), 2);
+        assert!(resolved.iter().all(|query| query.is_ingredient("resolve_module_query")));
+
+        let unrelated: Vec<_> = super::filter_by_ingredient(&executed, "infer_types").collect();
+        assert!(unrelated.is_empty());
+    }
+
+    #[test]
+    fn inference_on_snapshot_is_cancelled_by_parent_mutation() {
+        use salsa::ParallelDatabase;
+
+        let mut db = TestDb::new();
+        let snapshot = db.snapshot();
+
+        let worker = std::thread::spawn(move || {
+            salsa::Cancelled::catch(|| loop {
+                // `infer_types`/`semantic_index` only ever see a `&dyn Db`, so the checkpoint
+                // must be callable through the trait object, not just on a concrete `TestDb`.
+                let snapshot: &dyn super::Db = &*snapshot;
+                snapshot.unwind_if_cancelled();
+            })
+        });
+
+        // A real edit notifies the `Vfs` about a changed file, which in turn sets a salsa input
+        // at a new revision; `synthetic_write` is salsa's test-only stand-in for that, bumping
+        // the revision counter without needing a specific input to set. Either one cancels every
+        // outstanding snapshot the same way.
+        db.synthetic_write(Durability::LOW);
+
+        match worker.join().unwrap() {
+            Err(cancelled) => {
+                assert!(matches!(cancelled, salsa::Cancelled::PendingWrite));
+            }
+            Ok(()) => panic!("expected the snapshot's query to be cancelled"),
+        }
+    }
 }